@@ -0,0 +1,130 @@
+//! Deployment queueing and build orchestration.
+//!
+//! TODO: unpacking and compiling the crate, starting and supervising the built service, and
+//! restart-on-crash aren't implemented yet - `build_deployment` below only opens the queued
+//! artifact by path to show where that takes over.
+
+use std::path::PathBuf;
+use std::collections::HashMap;
+
+use tokio::sync::mpsc;
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+
+/// A deployment queued for building.
+///
+/// The uploaded artifact has already been streamed to disk and hashed by
+/// [`crate::handlers::post_service`] before this is constructed, so the build step reads it back
+/// from `data_path` instead of holding it in memory.
+#[derive(Clone, Debug)]
+pub struct Queued {
+    pub id: Uuid,
+    pub service_name: String,
+    pub service_id: Uuid,
+    pub data_path: PathBuf,
+    pub data_hash: String,
+    pub will_run_tests: bool,
+    pub tracing_context: HashMap<String, String>,
+}
+
+/// Where on disk a service's build artifacts and working directories live.
+#[derive(Clone)]
+pub struct StorageManager {
+    artifacts_path: PathBuf,
+}
+
+impl StorageManager {
+    pub fn new(artifacts_path: PathBuf) -> Self {
+        Self { artifacts_path }
+    }
+
+    /// The directory a service's deployment artifacts and build output are stored under.
+    pub fn service_build_path(&self, service_name: impl AsRef<str>) -> std::io::Result<PathBuf> {
+        let service_name = service_name.as_ref();
+
+        if service_name.is_empty() || service_name.contains(['/', '\\']) || service_name == ".." {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "service name is not a valid path segment",
+            ));
+        }
+
+        Ok(self.artifacts_path.join(service_name))
+    }
+}
+
+/// Queues deployments for building and (eventually) running.
+#[derive(Clone)]
+pub struct DeploymentManager {
+    storage_manager: StorageManager,
+    queue_tx: mpsc::UnboundedSender<Queued>,
+}
+
+impl DeploymentManager {
+    pub fn new(storage_manager: StorageManager) -> Self {
+        let (queue_tx, queue_rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(run_queue(queue_rx));
+
+        Self {
+            storage_manager,
+            queue_tx,
+        }
+    }
+
+    pub fn storage_manager(&self) -> &StorageManager {
+        &self.storage_manager
+    }
+
+    /// Queues a deployment for building on the background task spawned in
+    /// [`DeploymentManager::new`].
+    pub async fn queue_push(&self, queued: Queued) {
+        if self.queue_tx.send(queued).is_err() {
+            error!("deployment build queue receiver has shut down");
+        }
+    }
+
+    /// Stops a running or in-progress deployment.
+    ///
+    /// TODO: tearing down a running service process isn't implemented yet.
+    pub async fn kill(&self, _deployment_id: Uuid) {}
+}
+
+#[instrument(skip_all)]
+async fn run_queue(mut queue_rx: mpsc::UnboundedReceiver<Queued>) {
+    while let Some(queued) = queue_rx.recv().await {
+        build_deployment(queued).await;
+    }
+}
+
+/// Builds a queued deployment, reading the uploaded artifact from `data_path` on disk rather
+/// than an owned in-memory buffer - the file was already streamed there and hashed by
+/// `post_service` before this task ever sees it.
+async fn build_deployment(queued: Queued) {
+    let Queued {
+        id,
+        data_path,
+        data_hash,
+        ..
+    } = queued;
+
+    match tokio::fs::File::open(&data_path).await {
+        Ok(_artifact) => {
+            // TODO: unpack the tarball and compile the crate from here.
+            info!(
+                %id,
+                %data_hash,
+                path = %data_path.display(),
+                "building deployment from on-disk artifact"
+            );
+        }
+        Err(error) => {
+            error!(
+                %id,
+                %data_hash,
+                error = &error as &dyn std::error::Error,
+                "failed to open deployment artifact"
+            );
+        }
+    }
+}
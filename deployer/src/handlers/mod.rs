@@ -7,17 +7,20 @@ use axum::http::{Request, Response};
 use axum::middleware::from_extractor;
 use axum::routing::{get, post, Router};
 use axum::{extract::BodyStream, Json};
-use bytes::BufMut;
 use chrono::{TimeZone, Utc};
 use fqdn::FQDN;
 use futures::StreamExt;
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
 use opentelemetry::global;
 use opentelemetry_http::HeaderExtractor;
+use sha2::{Digest, Sha256};
 use shuttle_common::backends::metrics::Metrics;
 use shuttle_common::models::secret;
 use shuttle_common::project::ProjectName;
 use shuttle_common::LogItem;
 use shuttle_service::loader::clean_crate;
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
 use tower_http::auth::RequireAuthorizationLayer;
 use tower_http::trace::TraceLayer;
 use tracing::{debug, debug_span, error, field, instrument, trace, Span};
@@ -28,12 +31,32 @@ use crate::deployment::{DeploymentManager, Queued};
 use crate::persistence::{Deployment, Log, Persistence, SecretGetter, State};
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
 pub use {self::error::Error, self::error::Result};
 
 mod project;
 
+/// How often to refresh the deployment-state gauges exposed on `/metrics`.
+const METRICS_SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The global Prometheus recorder can only be installed once per process - calling
+/// `PrometheusBuilder::install_recorder` a second time (e.g. because `make_router` is called more
+/// than once, as in a test binary that builds several routers) panics. Cache the handle the first
+/// time around and hand out a clone of it afterwards.
+static PROMETHEUS_RECORDER: std::sync::OnceLock<PrometheusHandle> = std::sync::OnceLock::new();
+
+fn prometheus_recorder_handle() -> PrometheusHandle {
+    PROMETHEUS_RECORDER
+        .get_or_init(|| {
+            PrometheusBuilder::new()
+                .install_recorder()
+                .expect("failed to install Prometheus recorder")
+        })
+        .clone()
+}
+
 pub fn make_router(
     persistence: Persistence,
     deployment_manager: DeploymentManager,
@@ -41,6 +64,17 @@ pub fn make_router(
     admin_secret: String,
     project_name: ProjectName,
 ) -> Router {
+    let recorder_handle = prometheus_recorder_handle();
+
+    tokio::spawn(sample_deployment_metrics(persistence.clone()));
+
+    // `/metrics` has no `:project_name` segment, unlike every other route below, so it can't go
+    // through the `ProjectNameGuard` route_layer those routes are guarded by. Build it as its own
+    // router and merge it in once the guarded router is fully assembled.
+    let metrics_router = Router::new()
+        .route("/metrics", get(get_metrics))
+        .layer(Extension(recorder_handle));
+
     Router::new()
         .route("/projects/:project_name/services", get(list_services))
         .route(
@@ -67,14 +101,16 @@ pub fn make_router(
             "/projects/:project_name/secrets/:service_name",
             get(get_secrets),
         )
+        // No `/usage` route yet - see `crate::metering` for why it isn't wired in.
         .route("/projects/:project_name/clean", post(post_clean))
         .layer(Extension(persistence))
         .layer(Extension(deployment_manager))
         .layer(Extension(proxy_fqdn))
         .layer(RequireAuthorizationLayer::bearer(&admin_secret))
-        // This route should be below the auth bearer since it does not need authentication
+        // These routes should be below the auth bearer since they do not need authentication
         .route("/projects/:project_name/status", get(get_status))
         .route_layer(from_extractor::<Metrics>())
+        .route_layer(axum::middleware::from_fn(track_request_metrics))
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(|request: &Request<Body>| {
@@ -121,6 +157,7 @@ pub fn make_router(
         )
         .route_layer(from_extractor::<project::ProjectNameGuard>())
         .layer(Extension(project_name))
+        .merge(metrics_router)
 }
 
 #[instrument(skip_all)]
@@ -225,13 +262,54 @@ async fn post_service(
         address: None,
     };
 
-    let mut data = Vec::new();
+    // Artifacts for a service all live under its build path, keyed by content hash below so that
+    // two identical uploads share one file on disk instead of being stored twice.
+    let artifacts_dir = deployment_manager
+        .storage_manager()
+        .service_build_path(service.name.clone())
+        .map_err(anyhow::Error::new)?;
+    tokio::fs::create_dir_all(&artifacts_dir)
+        .await
+        .map_err(anyhow::Error::new)?;
+
+    // Stream into a per-upload temp file first, since the final, content-addressed name isn't
+    // known until the whole body has been hashed.
+    let tmp_path = artifacts_dir.join(format!("{id}.part"));
+    let mut cleanup_guard = TempArtifactGuard::new(tmp_path.clone());
+
+    let mut file = File::create(&tmp_path).await.map_err(anyhow::Error::new)?;
+    let mut hasher = Sha256::new();
+    let mut total_bytes = 0usize;
+
     while let Some(buf) = stream.next().await {
         let buf = buf?;
         debug!("Received {} bytes", buf.len());
-        data.put(buf);
+        total_bytes += buf.len();
+        hasher.update(&buf);
+        file.write_all(&buf).await.map_err(anyhow::Error::new)?;
     }
-    debug!("Received a total of {} bytes", data.len());
+    file.flush().await.map_err(anyhow::Error::new)?;
+    debug!("Received a total of {} bytes", total_bytes);
+    metrics::counter!("deployer_bytes_received_total", total_bytes as u64);
+
+    let data_hash = format!("{:x}", hasher.finalize());
+    let data_path = artifact_path_for_hash(&artifacts_dir, &data_hash);
+
+    if tokio::fs::try_exists(&data_path).await.unwrap_or(false) {
+        // An identical artifact is already on disk - drop the freshly uploaded duplicate and
+        // reuse the existing file rather than storing the same bytes twice.
+        trace!(%data_hash, "deduplicated identical deployment artifact");
+        tokio::fs::remove_file(&tmp_path)
+            .await
+            .map_err(anyhow::Error::new)?;
+    } else {
+        trace!(%data_hash, "hashed uploaded deployment artifact");
+        tokio::fs::rename(&tmp_path, &data_path)
+            .await
+            .map_err(anyhow::Error::new)?;
+    }
+    // The temp file has already been moved or removed above, so there's nothing left to clean up.
+    cleanup_guard.disarm();
 
     persistence.insert_deployment(deployment.clone()).await?;
 
@@ -239,7 +317,8 @@ async fn post_service(
         id,
         service_name: service.name,
         service_id: service.id,
-        data,
+        data_path,
+        data_hash,
         will_run_tests: !params.contains_key("no-test"),
         tracing_context: Default::default(),
     };
@@ -249,6 +328,48 @@ async fn post_service(
     Ok(Json(deployment.into()))
 }
 
+/// Builds the content-addressed path an artifact with the given hash is stored at, so that
+/// identical uploads are deduplicated on disk instead of being written twice.
+fn artifact_path_for_hash(artifacts_dir: &std::path::Path, hash: &str) -> PathBuf {
+    artifacts_dir.join(format!("{hash}.tar.gz"))
+}
+
+/// Removes the deployment artifact at `path` on drop, unless [`TempArtifactGuard::disarm`] has
+/// been called. Guards against leaving a partially written tarball on disk when an upload is
+/// aborted or fails before it reaches the build pipeline.
+struct TempArtifactGuard {
+    path: PathBuf,
+    armed: bool,
+}
+
+impl TempArtifactGuard {
+    fn new(path: PathBuf) -> Self {
+        Self { path, armed: true }
+    }
+
+    fn disarm(&mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for TempArtifactGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+
+        let path = self.path.clone();
+        tokio::spawn(async move {
+            if let Err(error) = tokio::fs::remove_file(&path).await {
+                error!(
+                    error = &error as &dyn std::error::Error,
+                    "failed to clean up aborted deployment artifact"
+                );
+            }
+        });
+    }
+}
+
 #[instrument(skip_all, fields(%project_name, %service_name))]
 async fn delete_service(
     Extension(persistence): Extension<Persistence>,
@@ -319,34 +440,184 @@ async fn delete_deployment(
     }
 }
 
+/// Bounds applied to a deployment's log backlog so that `get_logs` can page through it newest
+/// first instead of returning everything at once.
+///
+/// These are filtered in Rust over the full backlog from [`Persistence::get_deployment_logs`],
+/// not pushed into the SQL query - that needs a new persistence-layer method, so it still bounds
+/// the response size but not what's read out of the database. See
+/// [`filter_and_page_logs`].
+///
+/// The result must be ordered **newest first** (descending timestamp) so that `before`/`after`
+/// cursors and [`GetLogsResponse::next_cursor`] below are well-defined: the oldest row in a page
+/// is always the last one returned, and is the correct cursor to pass back as `before` to keep
+/// paging further into the past.
+pub struct LogsFilter {
+    pub since: Option<chrono::DateTime<Utc>>,
+    pub until: Option<chrono::DateTime<Utc>>,
+    pub limit: u32,
+    pub before: Option<chrono::DateTime<Utc>>,
+    pub after: Option<chrono::DateTime<Utc>>,
+    /// Accepted but not yet applied: filtering by level needs a level on [`Log`] (or on
+    /// [`LogItem`]) to filter against, and the persistence layer doesn't expose that representation
+    /// yet. Left as a follow-up alongside the SQL pushdown above.
+    pub level: Option<String>,
+}
+
+/// The largest page of logs a caller can request in one go.
+const MAX_LOGS_LIMIT: u32 = 1000;
+const DEFAULT_LOGS_LIMIT: u32 = 100;
+
+/// Clamps a caller-supplied `limit` to `(0, MAX_LOGS_LIMIT]`, defaulting to `DEFAULT_LOGS_LIMIT`
+/// when none was given.
+fn clamp_logs_limit(limit: Option<u32>) -> u32 {
+    limit.unwrap_or(DEFAULT_LOGS_LIMIT).clamp(1, MAX_LOGS_LIMIT)
+}
+
+#[derive(serde::Deserialize)]
+struct GetLogsParams {
+    /// Only return logs with a timestamp at or after this point.
+    since: Option<chrono::DateTime<Utc>>,
+    /// Only return logs with a timestamp at or before this point.
+    until: Option<chrono::DateTime<Utc>>,
+    /// Maximum number of logs to return. Defaults to [`DEFAULT_LOGS_LIMIT`], capped at
+    /// [`MAX_LOGS_LIMIT`].
+    limit: Option<u32>,
+    /// Page cursor: only return logs older than this timestamp. Pass the previous response's
+    /// `next_cursor` here to keep paging backwards through history.
+    before: Option<chrono::DateTime<Utc>>,
+    /// Page cursor: only return logs newer than this timestamp.
+    after: Option<chrono::DateTime<Utc>>,
+    /// Accepted but not applied: logs are not filtered by level.
+    level: Option<String>,
+}
+
+/// A page of logs in newest-first order, together with the cursor for fetching the next (older)
+/// page via `before`. `None` only when the page is empty; the page holding the oldest log still
+/// carries a cursor, and the request made with it returns an empty page.
+#[derive(serde::Serialize)]
+struct GetLogsResponse {
+    logs: Vec<LogItem>,
+    next_cursor: Option<chrono::DateTime<Utc>>,
+}
+
 #[instrument(skip_all, fields(%project_name, %deployment_id))]
 async fn get_logs(
     Extension(persistence): Extension<Persistence>,
     Path((project_name, deployment_id)): Path<(String, Uuid)>,
-) -> Result<Json<Vec<LogItem>>> {
+    Query(params): Query<GetLogsParams>,
+) -> Result<Json<GetLogsResponse>> {
     if let Some(deployment) = persistence.get_deployment(&deployment_id).await? {
-        Ok(Json(
-            persistence
-                .get_deployment_logs(&deployment.id)
-                .await?
-                .into_iter()
-                .filter_map(Into::into)
-                .collect(),
-        ))
+        let filter = LogsFilter {
+            since: params.since,
+            until: params.until,
+            limit: clamp_logs_limit(params.limit),
+            before: params.before,
+            after: params.after,
+            level: params.level,
+        };
+
+        // `logs` is newest-first (see the ordering contract on `LogsFilter`), so the oldest
+        // entry returned - the last one in the vec - is the correct `before` cursor for the
+        // next, older page.
+        let backlog = persistence.get_deployment_logs(&deployment.id).await?;
+        let logs = filter_and_page_logs(backlog, &filter);
+        let next_cursor = logs.last().map(|log| log.timestamp);
+
+        Ok(Json(GetLogsResponse {
+            logs: logs.into_iter().filter_map(Into::into).collect(),
+            next_cursor,
+        }))
     } else {
         Err(Error::NotFound)
     }
 }
 
+/// Sorts a deployment's full log backlog newest-first and applies [`LogsFilter`]'s time bounds
+/// and limit.
+///
+/// TODO(follow-up): push these bounds into the SQL query via a new
+/// `Persistence::get_deployment_logs_filtered` method instead - that's the actual fix for memory
+/// use on huge deployments, but it needs a persistence-layer change first.
+fn filter_and_page_logs(mut logs: Vec<Log>, filter: &LogsFilter) -> Vec<Log> {
+    logs.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+    logs.into_iter()
+        .filter(|log| filter.since.map_or(true, |since| log.timestamp >= since))
+        .filter(|log| filter.until.map_or(true, |until| log.timestamp <= until))
+        .filter(|log| filter.before.map_or(true, |before| log.timestamp < before))
+        .filter(|log| filter.after.map_or(true, |after| log.timestamp > after))
+        .take(filter.limit as usize)
+        .collect()
+}
+
+#[derive(serde::Deserialize)]
+struct LogsSubscribeParams {
+    /// Resume the stream from just after this timestamp instead of replaying the whole backlog.
+    /// A reconnecting client should echo back the timestamp of the last log item it saw.
+    since: Option<chrono::DateTime<Utc>>,
+    /// How many log lines sharing the `since` timestamp the client already saw. Timestamps are
+    /// not unique per log line, so a bare `since` is not enough to resume without either
+    /// dropping or repeating lines from the same instant; the client should count how many
+    /// lines it received at the max timestamp it saw and echo that count back here. When omitted,
+    /// every line at exactly `since` counts as already seen.
+    since_seq: Option<usize>,
+}
+
 async fn get_logs_subscribe(
     Extension(persistence): Extension<Persistence>,
     Path((_project_name, deployment_id)): Path<(String, Uuid)>,
+    Query(params): Query<LogsSubscribeParams>,
     ws_upgrade: ws::WebSocketUpgrade,
 ) -> axum::response::Response {
-    ws_upgrade.on_upgrade(move |s| logs_websocket_handler(s, persistence, deployment_id))
+    let cursor = LogsCursor {
+        timestamp: params.since,
+        seq: params.since_seq.unwrap_or(usize::MAX),
+    };
+
+    ws_upgrade.on_upgrade(move |s| logs_websocket_handler(s, persistence, deployment_id, cursor))
+}
+
+/// A resume point for [`logs_websocket_handler`]. `seq` disambiguates log lines sharing the same
+/// `timestamp`, which alone isn't unique.
+#[derive(Clone, Copy)]
+struct LogsCursor {
+    timestamp: Option<chrono::DateTime<Utc>>,
+    seq: usize,
+}
+
+/// Whether a backlog entry was already sent to the client before it reconnected.
+/// `skip_remaining` counts down as entries exactly at `cursor_timestamp` are skipped, so ties
+/// resolve precisely instead of dropping or replaying a whole timestamp bucket.
+fn should_skip_backlog_entry(
+    log_timestamp: chrono::DateTime<Utc>,
+    cursor_timestamp: chrono::DateTime<Utc>,
+    skip_remaining: &mut usize,
+) -> bool {
+    if log_timestamp < cursor_timestamp {
+        return true;
+    }
+
+    if log_timestamp == cursor_timestamp && *skip_remaining > 0 {
+        *skip_remaining -= 1;
+        return true;
+    }
+
+    false
 }
 
-async fn logs_websocket_handler(mut s: WebSocket, persistence: Persistence, id: Uuid) {
+/// How often to ping the client to check that the socket is still alive.
+const LOGS_PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long to wait for a pong before giving up on the connection.
+const LOGS_PONG_TIMEOUT: Duration = Duration::from_secs(10);
+
+async fn logs_websocket_handler(
+    mut s: WebSocket,
+    persistence: Persistence,
+    id: Uuid,
+    cursor: LogsCursor,
+) {
     let mut log_recv = persistence.get_log_subscriber();
     let backlog = match persistence.get_deployment_logs(&id).await {
         Ok(backlog) => backlog,
@@ -365,9 +636,16 @@ async fn logs_websocket_handler(mut s: WebSocket, persistence: Persistence, id:
     };
 
     // Unwrap is safe because it only returns None for out of range numbers or invalid nanosecond
-    let mut last_timestamp = Utc.timestamp_opt(0, 0).unwrap();
+    let cursor_timestamp = cursor
+        .timestamp
+        .unwrap_or_else(|| Utc.timestamp_opt(0, 0).unwrap());
+    let mut skip_remaining = cursor.seq;
+    let mut last_timestamp = cursor_timestamp;
 
     for log in backlog.into_iter() {
+        if should_skip_backlog_entry(log.timestamp, cursor_timestamp, &mut skip_remaining) {
+            continue;
+        }
         last_timestamp = log.timestamp;
         if let Some(log_item) = Option::<LogItem>::from(log) {
             let msg = serde_json::to_string(&log_item).expect("to convert log item to json");
@@ -380,17 +658,49 @@ async fn logs_websocket_handler(mut s: WebSocket, persistence: Persistence, id:
         }
     }
 
-    while let Ok(log) = log_recv.recv().await {
-        trace!(?log, "received log from broadcast channel");
-
-        if log.id == id && log.timestamp > last_timestamp {
-            if let Some(log_item) = Option::<LogItem>::from(Log::from(log)) {
-                let msg = serde_json::to_string(&log_item).expect("to convert log item to json");
-                let sent = s.send(ws::Message::Text(msg)).await;
+    let mut ping_interval = tokio::time::interval(LOGS_PING_INTERVAL);
+    // The first tick fires immediately, which we don't want since the client just connected
+    ping_interval.tick().await;
+    let mut awaiting_pong = false;
+
+    loop {
+        tokio::select! {
+            log = log_recv.recv() => {
+                let log = match log {
+                    Ok(log) => log,
+                    Err(_) => break,
+                };
+
+                trace!(?log, "received log from broadcast channel");
+
+                if log.id == id && log.timestamp > last_timestamp {
+                    if let Some(log_item) = Option::<LogItem>::from(Log::from(log)) {
+                        let msg = serde_json::to_string(&log_item).expect("to convert log item to json");
+                        let sent = s.send(ws::Message::Text(msg)).await;
+
+                        // Client disconnected?
+                        if sent.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            _ = ping_interval.tick() => {
+                if awaiting_pong {
+                    debug!("client did not respond to ping in time, closing log subscription");
+                    break;
+                }
 
-                // Client disconnected?
-                if sent.is_err() {
-                    return;
+                if s.send(ws::Message::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+                awaiting_pong = true;
+            }
+            msg = tokio::time::timeout(LOGS_PONG_TIMEOUT, s.recv()), if awaiting_pong => {
+                match msg {
+                    Ok(Some(Ok(ws::Message::Pong(_)))) => awaiting_pong = false,
+                    Ok(Some(Ok(_))) => {}
+                    _ => break,
                 }
             }
         }
@@ -422,6 +732,8 @@ async fn post_clean(
     Extension(deployment_manager): Extension<DeploymentManager>,
     Path(project_name): Path<String>,
 ) -> Result<Json<Vec<String>>> {
+    let start = std::time::Instant::now();
+
     let project_path = deployment_manager
         .storage_manager()
         .service_build_path(project_name)
@@ -429,9 +741,188 @@ async fn post_clean(
 
     let lines = clean_crate(&project_path, true)?;
 
+    metrics::histogram!("deployer_clean_duration_seconds", start.elapsed().as_secs_f64());
+
     Ok(Json(lines))
 }
 
 async fn get_status() -> String {
     "Ok".to_string()
 }
+
+async fn get_metrics(Extension(recorder_handle): Extension<PrometheusHandle>) -> String {
+    recorder_handle.render()
+}
+
+/// Records a request-latency histogram for every matched route, labelled by method, path and
+/// status code. `/metrics` is merged into the router after this layer and is not recorded. Mounted with `route_layer` so [`MatchedPath`] is already in the request
+/// extensions by the time this runs.
+async fn track_request_metrics(
+    request: Request<Body>,
+    next: axum::middleware::Next<Body>,
+) -> Response<BoxBody> {
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_default();
+    let method = request.method().clone();
+
+    let start = std::time::Instant::now();
+    let response = next.run(request).await;
+    let latency = start.elapsed();
+
+    metrics::histogram!(
+        "deployer_request_duration_seconds",
+        latency.as_secs_f64(),
+        "method" => method.to_string(),
+        "path" => path,
+        "status" => response.status().as_u16().to_string(),
+    );
+
+    response
+}
+
+/// Maps a [`State`] to the label used on the `deployer_deployments` gauge.
+fn deployment_state_label(state: &State) -> &'static str {
+    match state {
+        State::Queued => "queued",
+        State::Building => "building",
+        State::Running => "running",
+        State::Crashed => "crashed",
+        _ => "other",
+    }
+}
+
+/// Periodically samples the number of deployments in each [`State`] across every service and
+/// publishes it as a `deployer_deployments` gauge.
+///
+/// Queue depth and build-duration metrics are not implemented: both need a method on
+/// `DeploymentManager` (`queue_len`, and per-build timing in the build loop) that `deployment.rs`
+/// doesn't expose yet. Descoped rather than faked.
+async fn sample_deployment_metrics(persistence: Persistence) {
+    let mut interval = tokio::time::interval(METRICS_SAMPLE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let services = match persistence.get_all_services().await {
+            Ok(services) => services,
+            Err(error) => {
+                error!(
+                    error = &error as &dyn std::error::Error,
+                    "failed to list services while sampling deployment metrics"
+                );
+                continue;
+            }
+        };
+
+        // Start every label at zero so a state with no deployments left is published as 0
+        // instead of keeping its last sampled value.
+        let mut counts: HashMap<&'static str, u64> = ["queued", "building", "running", "crashed", "other"]
+            .into_iter()
+            .map(|state| (state, 0))
+            .collect();
+
+        for service in services {
+            match persistence.get_deployments(&service.id).await {
+                Ok(deployments) => {
+                    for deployment in deployments {
+                        *counts.entry(deployment_state_label(&deployment.state)).or_insert(0) += 1;
+                    }
+                }
+                Err(error) => {
+                    error!(
+                        error = &error as &dyn std::error::Error,
+                        "failed to list deployments while sampling deployment metrics"
+                    );
+                }
+            }
+        }
+
+        for (state, count) in counts {
+            metrics::gauge!("deployer_deployments", count as f64, "state" => state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backlog_resume_does_not_drop_ties_at_the_cursor_timestamp() {
+        let t0 = Utc.timestamp_opt(0, 0).unwrap();
+        let t1 = Utc.timestamp_opt(1, 0).unwrap();
+
+        // Three lines shared timestamp t0; the client already saw the first two of them
+        // before reconnecting, so it resumes with `since = t0, since_seq = 2`.
+        let mut skip_remaining = 2;
+
+        assert!(should_skip_backlog_entry(t0, t0, &mut skip_remaining));
+        assert!(should_skip_backlog_entry(t0, t0, &mut skip_remaining));
+        assert!(!should_skip_backlog_entry(t0, t0, &mut skip_remaining));
+        assert!(!should_skip_backlog_entry(t1, t0, &mut skip_remaining));
+    }
+
+    #[test]
+    fn backlog_resume_skips_everything_strictly_before_the_cursor() {
+        let t0 = Utc.timestamp_opt(0, 0).unwrap();
+        let t1 = Utc.timestamp_opt(1, 0).unwrap();
+        let mut skip_remaining = 0;
+
+        assert!(should_skip_backlog_entry(t0, t1, &mut skip_remaining));
+    }
+
+    #[test]
+    fn logs_limit_defaults_and_is_clamped_to_the_max() {
+        assert_eq!(clamp_logs_limit(None), DEFAULT_LOGS_LIMIT);
+        assert_eq!(clamp_logs_limit(Some(0)), 1);
+        assert_eq!(clamp_logs_limit(Some(50)), 50);
+        assert_eq!(clamp_logs_limit(Some(u32::MAX)), MAX_LOGS_LIMIT);
+    }
+
+    #[test]
+    fn deployment_state_label_is_lowercase() {
+        assert_eq!(deployment_state_label(&State::Queued), "queued");
+    }
+
+    #[test]
+    fn artifacts_with_the_same_hash_resolve_to_the_same_path() {
+        let dir = std::path::Path::new("/var/lib/shuttle/artifacts");
+
+        assert_eq!(
+            artifact_path_for_hash(dir, "deadbeef"),
+            artifact_path_for_hash(dir, "deadbeef")
+        );
+        assert_ne!(
+            artifact_path_for_hash(dir, "deadbeef"),
+            artifact_path_for_hash(dir, "cafef00d")
+        );
+    }
+
+    #[tokio::test]
+    async fn temp_artifact_guard_removes_the_file_unless_disarmed() {
+        let path = std::env::temp_dir().join(format!("shuttle-test-{}", Uuid::new_v4()));
+        tokio::fs::write(&path, b"test").await.unwrap();
+
+        {
+            let _guard = TempArtifactGuard::new(path.clone());
+        }
+        // The guard's cleanup is spawned onto the runtime rather than awaited, so give it a
+        // moment to run before asserting.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!path.exists());
+
+        let path = std::env::temp_dir().join(format!("shuttle-test-{}", Uuid::new_v4()));
+        tokio::fs::write(&path, b"test").await.unwrap();
+
+        {
+            let mut guard = TempArtifactGuard::new(path.clone());
+            guard.disarm();
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(path.exists());
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
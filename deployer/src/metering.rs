@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::persistence::Persistence;
+
+/// Unit costs used to turn accrued usage into an estimated bill, plus how often a service's
+/// counters roll over to a fresh billing period.
+#[derive(Clone, Copy, Debug)]
+pub struct MeteringConfig {
+    pub cost_per_1000_requests: f64,
+    pub cost_per_cpu_second: f64,
+    pub billing_period: Duration,
+}
+
+impl Default for MeteringConfig {
+    fn default() -> Self {
+        Self {
+            cost_per_1000_requests: 0.10,
+            cost_per_cpu_second: 0.000_025,
+            billing_period: Duration::from_secs(60 * 60 * 24 * 30),
+        }
+    }
+}
+
+/// How often the background loop samples every service and accrues its usage.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Accrued usage for a service over the current billing period, together with an estimated cost
+/// derived from [`MeteringConfig`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Usage {
+    pub service_id: Uuid,
+    pub period_start: DateTime<Utc>,
+    pub request_count: u64,
+    pub cpu_seconds: f64,
+    pub estimated_cost: f64,
+}
+
+/// One resource-consumption sample for a single running deployment, taken by whatever is running
+/// the service (e.g. the runtime process supervisor). `request_count` and `cpu_seconds` are
+/// deltas since the last sample, not running totals, so they can be accrued directly.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UsageSample {
+    pub request_count: u64,
+    pub cpu_seconds: f64,
+}
+
+/// A service's running totals for the current billing period.
+#[derive(Clone, Copy, Debug)]
+struct UsageRecord {
+    period_start: DateTime<Utc>,
+    request_count: u64,
+    cpu_seconds: f64,
+}
+
+impl UsageRecord {
+    fn starting_now(now: DateTime<Utc>) -> Self {
+        Self {
+            period_start: now,
+            request_count: 0,
+            cpu_seconds: 0.0,
+        }
+    }
+}
+
+/// Tracks per-service request count and CPU-seconds usage and turns it into an estimated cost.
+///
+/// Not wired into `make_router` yet, and no `/usage` route is registered. Usage needs to land in
+/// a `service_usage` persistence table with an atomic upsert, so a deployer restart doesn't lose
+/// accrued usage between ticks - the in-memory ledger below can't stand in for that without
+/// losing exactly the data billing depends on. Land the table and upsert first, have
+/// [`MeteringManager`] accrue into it instead of the in-memory map, then wire the route up.
+#[derive(Clone)]
+pub struct MeteringManager {
+    #[allow(dead_code)]
+    persistence: Persistence,
+    config: MeteringConfig,
+    usage: Arc<Mutex<HashMap<Uuid, UsageRecord>>>,
+}
+
+impl MeteringManager {
+    pub fn new(persistence: Persistence, config: MeteringConfig) -> Self {
+        Self {
+            persistence,
+            config,
+            usage: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Spawns the periodic billing loop, which lists every service from [`Persistence`] once per
+    /// `SAMPLE_INTERVAL` and accrues a usage sample for each.
+    pub fn spawn_billing_loop(self) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+
+            loop {
+                interval.tick().await;
+                self.accrue_usage_tick().await;
+            }
+        });
+    }
+
+    async fn accrue_usage_tick(&self) {
+        let services = match self.persistence.get_all_services().await {
+            Ok(services) => services,
+            Err(error) => {
+                tracing::error!(
+                    error = &error as &dyn std::error::Error,
+                    "failed to list services while accruing metering usage"
+                );
+                return;
+            }
+        };
+
+        for service in services {
+            let sample = self.sample_resource_usage(&service.id).await;
+            self.accrue(service.id, sample, Utc::now());
+        }
+    }
+
+    fn accrue(&self, service_id: Uuid, sample: UsageSample, now: DateTime<Utc>) {
+        let mut usage = self.usage.lock().unwrap();
+        accrue_into(&mut usage, &self.config, service_id, sample, now);
+    }
+
+    /// Samples a service's resource consumption since the last tick. Left as a stub here: the
+    /// real per-deployment request/CPU counters live wherever the running service's process is
+    /// supervised, which this module doesn't have visibility into.
+    async fn sample_resource_usage(&self, _service_id: &Uuid) -> UsageSample {
+        UsageSample::default()
+    }
+
+    /// Returns the service's accrued usage for the current billing period and an estimated cost.
+    pub async fn get_current_usage(&self, service_id: &Uuid) -> Usage {
+        let now = Utc::now();
+        let usage = self.usage.lock().unwrap();
+
+        match usage.get(service_id) {
+            Some(record) => Usage {
+                service_id: *service_id,
+                period_start: record.period_start,
+                request_count: record.request_count,
+                cpu_seconds: record.cpu_seconds,
+                estimated_cost: estimated_cost(&self.config, record.request_count, record.cpu_seconds),
+            },
+            None => Usage {
+                service_id: *service_id,
+                period_start: now,
+                request_count: 0,
+                cpu_seconds: 0.0,
+                estimated_cost: 0.0,
+            },
+        }
+    }
+}
+
+fn estimated_cost(config: &MeteringConfig, request_count: u64, cpu_seconds: f64) -> f64 {
+    (request_count as f64 / 1000.0) * config.cost_per_1000_requests + cpu_seconds * config.cost_per_cpu_second
+}
+
+fn accrue_into(
+    usage: &mut HashMap<Uuid, UsageRecord>,
+    config: &MeteringConfig,
+    service_id: Uuid,
+    sample: UsageSample,
+    now: DateTime<Utc>,
+) {
+    let billing_period =
+        chrono::Duration::from_std(config.billing_period).unwrap_or_else(|_| chrono::Duration::zero());
+    let record = usage
+        .entry(service_id)
+        .or_insert_with(|| UsageRecord::starting_now(now));
+
+    if now - record.period_start >= billing_period {
+        *record = UsageRecord::starting_now(now);
+    }
+
+    record.request_count += sample.request_count;
+    record.cpu_seconds += sample.cpu_seconds;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_has_sane_non_zero_unit_costs() {
+        let config = MeteringConfig::default();
+
+        assert!(config.cost_per_1000_requests > 0.0);
+        assert!(config.cost_per_cpu_second > 0.0);
+        assert!(config.billing_period > Duration::ZERO);
+    }
+
+    #[test]
+    fn estimated_cost_is_the_sum_of_request_and_cpu_cost() {
+        let config = MeteringConfig {
+            cost_per_1000_requests: 1.0,
+            cost_per_cpu_second: 2.0,
+            billing_period: Duration::from_secs(1),
+        };
+
+        // 2000 requests at $1/1000 = $2, plus 3 cpu-seconds at $2/s = $6, for $8 total.
+        assert_eq!(estimated_cost(&config, 2_000, 3.0), 8.0);
+    }
+
+    #[test]
+    fn accrual_resets_once_the_billing_period_has_elapsed() {
+        let config = MeteringConfig {
+            cost_per_1000_requests: 1.0,
+            cost_per_cpu_second: 1.0,
+            billing_period: Duration::from_secs(60),
+        };
+        let mut usage = HashMap::new();
+        let service_id = Uuid::new_v4();
+        let start = Utc::now();
+
+        accrue_into(
+            &mut usage,
+            &config,
+            service_id,
+            UsageSample {
+                request_count: 10,
+                cpu_seconds: 1.0,
+            },
+            start,
+        );
+        accrue_into(
+            &mut usage,
+            &config,
+            service_id,
+            UsageSample {
+                request_count: 5,
+                cpu_seconds: 1.0,
+            },
+            start + chrono::Duration::seconds(30),
+        );
+
+        assert_eq!(usage.get(&service_id).unwrap().request_count, 15);
+
+        accrue_into(
+            &mut usage,
+            &config,
+            service_id,
+            UsageSample {
+                request_count: 3,
+                cpu_seconds: 0.5,
+            },
+            start + chrono::Duration::seconds(120),
+        );
+
+        assert_eq!(usage.get(&service_id).unwrap().request_count, 3);
+    }
+}